@@ -0,0 +1,489 @@
+use serde_yaml::{Mapping, Value};
+
+use crate::subscriptions::{parse_mbps, Node};
+
+// ==========================================================
+// Clash / Clash.Meta YAML 订阅解析
+// ==========================================================
+
+// 判断内容是否为 Clash 配置 (顶层包含 proxies 列表)
+pub fn is_clash_yaml(text: &str) -> bool {
+    text.lines()
+        .any(|line| line.trim_end() == "proxies:" || line.starts_with("proxies: ["))
+}
+
+// 解析 Clash 配置中的 proxies 列表，无法识别的条目直接跳过
+pub fn parse_clash_yaml(text: &str) -> Result<Vec<Node>, String> {
+    let doc: Value = serde_yaml::from_str(text).map_err(|e| format!("YAML 解析失败: {}", e))?;
+    let proxies = doc
+        .get("proxies")
+        .and_then(|p| p.as_sequence())
+        .ok_or("未找到 proxies 列表")?;
+
+    Ok(proxies
+        .iter()
+        .filter_map(|p| p.as_mapping())
+        .filter_map(parse_clash_proxy)
+        .collect())
+}
+
+fn parse_clash_proxy(proxy: &Mapping) -> Option<Node> {
+    let proxy_type = get_str(proxy, "type")?;
+    let protocol = match proxy_type.as_str() {
+        "ss" | "vmess" | "trojan" | "vless" | "tuic" => proxy_type.clone(),
+        "hysteria2" | "hy2" => "hysteria2".to_string(),
+        _ => return None,
+    };
+
+    let mut node = Node {
+        protocol,
+        name: get_str(proxy, "name").unwrap_or_else(|| "Unknown".to_string()),
+        address: get_str(proxy, "server")?,
+        port: get_u16(proxy, "port")?,
+        sni: get_str(proxy, "sni").or_else(|| get_str(proxy, "servername")),
+        allow_insecure: get_bool(proxy, "skip-cert-verify"),
+        fingerprint: get_str(proxy, "client-fingerprint"),
+        alpn: get_str_list(proxy, "alpn"),
+        ..Default::default()
+    };
+
+    match node.protocol.as_str() {
+        "ss" => {
+            node.cipher = get_str(proxy, "cipher");
+            node.password = get_str(proxy, "password");
+            if let Some(plugin) = get_str(proxy, "plugin") {
+                let opts = proxy.get("plugin-opts").and_then(|o| o.as_mapping());
+                let (name, plugin_opts) = convert_ss_plugin(&plugin, opts)?;
+                node.plugin = Some(name);
+                node.plugin_opts = plugin_opts;
+            }
+        }
+        "vmess" => {
+            node.uuid = get_str(proxy, "uuid");
+            node.alter_id = get_u16(proxy, "alterId").unwrap_or(0);
+            node.cipher = get_str(proxy, "cipher").or_else(|| Some("auto".to_string()));
+            if get_bool(proxy, "tls") {
+                node.security = Some("tls".to_string());
+            }
+            apply_clash_transport(proxy, &mut node);
+        }
+        "trojan" => {
+            node.password = get_str(proxy, "password");
+            apply_clash_transport(proxy, &mut node);
+        }
+        "vless" => {
+            node.uuid = get_str(proxy, "uuid");
+            node.flow = get_str(proxy, "flow");
+            if let Some(reality) = proxy.get("reality-opts").and_then(|r| r.as_mapping()) {
+                node.security = Some("reality".to_string());
+                node.public_key = Some(get_str(reality, "public-key")?);
+                node.short_id = get_str(reality, "short-id");
+            } else if get_bool(proxy, "tls") {
+                node.security = Some("tls".to_string());
+            }
+            apply_clash_transport(proxy, &mut node);
+        }
+        "hysteria2" => {
+            node.password = get_str(proxy, "password").or_else(|| get_str(proxy, "auth"));
+            node.ports = get_str(proxy, "ports");
+            node.obfs = get_str(proxy, "obfs");
+            node.obfs_password = get_str(proxy, "obfs-password");
+            node.up_mbps = get_str(proxy, "up").and_then(|v| parse_mbps(&v));
+            node.down_mbps = get_str(proxy, "down").and_then(|v| parse_mbps(&v));
+        }
+        "tuic" => {
+            node.uuid = get_str(proxy, "uuid");
+            node.password = get_str(proxy, "password");
+            node.congestion_control = get_str(proxy, "congestion-controller");
+            node.udp_relay_mode = get_str(proxy, "udp-relay-mode");
+            node.zero_rtt_handshake = get_bool(proxy, "reduce-rtt");
+        }
+        _ => {}
+    }
+
+    Some(node)
+}
+
+// network + ws-opts / grpc-opts / h2-opts / http-opts
+fn apply_clash_transport(proxy: &Mapping, node: &mut Node) {
+    let opts = |key: &str| proxy.get(key).and_then(|o| o.as_mapping());
+
+    match get_str(proxy, "network").as_deref() {
+        Some("ws") => {
+            node.network = Some("ws".to_string());
+            if let Some(ws) = opts("ws-opts") {
+                if get_bool(ws, "v2ray-http-upgrade") {
+                    node.network = Some("httpupgrade".to_string());
+                }
+                node.path = get_str(ws, "path");
+                node.host = ws
+                    .get("headers")
+                    .and_then(|h| h.as_mapping())
+                    .and_then(|h| get_str(h, "Host").or_else(|| get_str(h, "host")));
+                // Clash 用 max-early-data 表示 early data，统一转换为 ?ed= 形式
+                if let Some(ed) = get_u16(ws, "max-early-data") {
+                    let path = node.path.clone().unwrap_or_else(|| "/".to_string());
+                    node.path = Some(format!("{}?ed={}", path, ed));
+                }
+            }
+        }
+        Some("grpc") => {
+            node.network = Some("grpc".to_string());
+            node.path = opts("grpc-opts").and_then(|g| get_str(g, "grpc-service-name"));
+        }
+        Some("h2") => {
+            node.network = Some("http".to_string());
+            if let Some(h2) = opts("h2-opts") {
+                node.path = get_str(h2, "path");
+                node.host = get_str_list(h2, "host").map(|h| h.join(","));
+            }
+        }
+        Some("http") => {
+            node.network = Some("http".to_string());
+            if let Some(http) = opts("http-opts") {
+                node.path = get_str_list(http, "path").and_then(|p| p.into_iter().next());
+                node.host = http
+                    .get("headers")
+                    .and_then(|h| h.as_mapping())
+                    .and_then(|h| get_str_list(h, "Host"))
+                    .map(|h| h.join(","));
+            }
+        }
+        _ => {}
+    }
+}
+
+// 把 Clash 的 plugin / plugin-opts 转换为 SIP003 插件参数
+fn convert_ss_plugin(plugin: &str, opts: Option<&Mapping>) -> Option<(String, Option<String>)> {
+    let mut parts = Vec::new();
+    let name = match plugin {
+        "obfs" | "obfs-local" | "simple-obfs" => {
+            if let Some(opts) = opts {
+                if let Some(mode) = get_str(opts, "mode") {
+                    parts.push(format!("obfs={}", mode));
+                }
+                if let Some(host) = get_str(opts, "host") {
+                    parts.push(format!("obfs-host={}", host));
+                }
+            }
+            "obfs-local"
+        }
+        "v2ray-plugin" => {
+            if let Some(opts) = opts {
+                if let Some(mode) = get_str(opts, "mode") {
+                    parts.push(format!("mode={}", mode));
+                }
+                if let Some(host) = get_str(opts, "host") {
+                    parts.push(format!("host={}", host));
+                }
+                if let Some(path) = get_str(opts, "path") {
+                    parts.push(format!("path={}", path));
+                }
+                if get_bool(opts, "tls") {
+                    parts.push("tls".to_string());
+                }
+                if get_bool(opts, "mux") {
+                    parts.push("mux=1".to_string());
+                }
+            }
+            "v2ray-plugin"
+        }
+        _ => return None,
+    };
+
+    let opts = if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(";"))
+    };
+    Some((name.to_string(), opts))
+}
+
+// ==========================================================
+// 导出为 Clash proxies YAML
+// ==========================================================
+
+// names 与 nodes 一一对应，需保证唯一
+pub fn to_clash_yaml(nodes: &[Node], names: &[String]) -> Result<String, String> {
+    let proxies: Vec<Value> = nodes
+        .iter()
+        .zip(names)
+        .filter_map(|(node, name)| node_to_clash_proxy(node, name))
+        .map(Value::Mapping)
+        .collect();
+
+    let mut doc = Mapping::new();
+    doc.insert("proxies".into(), Value::Sequence(proxies));
+    serde_yaml::to_string(&doc).map_err(|e| format!("YAML 生成失败: {}", e))
+}
+
+fn node_to_clash_proxy(node: &Node, name: &str) -> Option<Mapping> {
+    let mut proxy = Mapping::new();
+    set(&mut proxy, "name", name);
+    set(&mut proxy, "type", node.protocol.as_str());
+    set(&mut proxy, "server", node.address.as_str());
+    set(&mut proxy, "port", node.port);
+
+    match node.protocol.as_str() {
+        "ss" => {
+            set(&mut proxy, "cipher", node.cipher.as_deref()?);
+            set(&mut proxy, "password", node.password.as_deref()?);
+            if let Some(plugin) = &node.plugin {
+                let (name, opts) = to_clash_ss_plugin(plugin, node.plugin_opts.as_deref());
+                set(&mut proxy, "plugin", name);
+                set(&mut proxy, "plugin-opts", opts);
+            }
+        }
+        "vmess" => {
+            set(&mut proxy, "uuid", node.uuid.as_deref()?);
+            set(&mut proxy, "alterId", node.alter_id);
+            set(
+                &mut proxy,
+                "cipher",
+                node.cipher.as_deref().unwrap_or("auto"),
+            );
+            set_tls(&mut proxy, node, "servername");
+            set_clash_transport(&mut proxy, node);
+        }
+        "trojan" => {
+            set(&mut proxy, "password", node.password.as_deref()?);
+            set_tls(&mut proxy, node, "sni");
+            set_clash_transport(&mut proxy, node);
+        }
+        "vless" => {
+            set(&mut proxy, "uuid", node.uuid.as_deref()?);
+            if let Some(flow) = &node.flow {
+                set(&mut proxy, "flow", flow.as_str());
+            }
+            set_tls(&mut proxy, node, "servername");
+            if node.security.as_deref() == Some("reality") {
+                let mut reality = Mapping::new();
+                set(&mut reality, "public-key", node.public_key.as_deref()?);
+                if let Some(sid) = &node.short_id {
+                    set(&mut reality, "short-id", sid.as_str());
+                }
+                set(&mut proxy, "reality-opts", reality);
+            }
+            set_clash_transport(&mut proxy, node);
+        }
+        "hysteria2" => {
+            set(&mut proxy, "password", node.password.as_deref()?);
+            if let Some(ports) = &node.ports {
+                set(&mut proxy, "ports", ports.as_str());
+            }
+            if let Some(obfs) = &node.obfs {
+                set(&mut proxy, "obfs", obfs.as_str());
+            }
+            if let Some(obfs_password) = &node.obfs_password {
+                set(&mut proxy, "obfs-password", obfs_password.as_str());
+            }
+            if let Some(up) = node.up_mbps {
+                set(&mut proxy, "up", format!("{} Mbps", up));
+            }
+            if let Some(down) = node.down_mbps {
+                set(&mut proxy, "down", format!("{} Mbps", down));
+            }
+            set_sni(&mut proxy, node, "sni");
+        }
+        "tuic" => {
+            set(&mut proxy, "uuid", node.uuid.as_deref()?);
+            set(
+                &mut proxy,
+                "password",
+                node.password.as_deref().unwrap_or(""),
+            );
+            if let Some(cc) = &node.congestion_control {
+                set(&mut proxy, "congestion-controller", cc.as_str());
+            }
+            if let Some(mode) = &node.udp_relay_mode {
+                set(&mut proxy, "udp-relay-mode", mode.as_str());
+            }
+            if node.zero_rtt_handshake {
+                set(&mut proxy, "reduce-rtt", true);
+            }
+            set_sni(&mut proxy, node, "sni");
+        }
+        _ => return None,
+    }
+
+    Some(proxy)
+}
+
+// vmess 使用 servername，trojan 使用 sni；trojan 总是启用 TLS，不需要 tls 字段
+fn set_tls(proxy: &mut Mapping, node: &Node, sni_key: &str) {
+    if node.protocol != "trojan" {
+        if node.security.is_none() {
+            return;
+        }
+        set(proxy, "tls", true);
+    }
+    set_sni(proxy, node, sni_key);
+    if let Some(fp) = &node.fingerprint {
+        set(proxy, "client-fingerprint", fp.as_str());
+    }
+}
+
+// sni / skip-cert-verify / alpn
+fn set_sni(proxy: &mut Mapping, node: &Node, sni_key: &str) {
+    if let Some(sni) = &node.sni {
+        set(proxy, sni_key, sni.as_str());
+    }
+    if node.allow_insecure {
+        set(proxy, "skip-cert-verify", true);
+    }
+    if let Some(alpn) = &node.alpn {
+        set(proxy, "alpn", alpn.clone());
+    }
+}
+
+// apply_clash_transport 的逆操作
+fn set_clash_transport(proxy: &mut Mapping, node: &Node) {
+    match node.network.as_deref() {
+        Some(network @ ("ws" | "httpupgrade")) => {
+            set(proxy, "network", "ws");
+            let mut ws = Mapping::new();
+            let path = node.path.as_deref().unwrap_or("/");
+            match path.split_once("?ed=") {
+                Some((p, ed)) => {
+                    set(&mut ws, "path", p);
+                    if let Ok(ed) = ed.parse::<u32>() {
+                        set(&mut ws, "max-early-data", ed);
+                        set(&mut ws, "early-data-header-name", "Sec-WebSocket-Protocol");
+                    }
+                }
+                None => set(&mut ws, "path", path),
+            }
+            if let Some(host) = &node.host {
+                let mut headers = Mapping::new();
+                set(&mut headers, "Host", host.as_str());
+                set(&mut ws, "headers", headers);
+            }
+            if network == "httpupgrade" {
+                set(&mut ws, "v2ray-http-upgrade", true);
+            }
+            set(proxy, "ws-opts", ws);
+        }
+        Some("grpc") => {
+            set(proxy, "network", "grpc");
+            let mut grpc = Mapping::new();
+            if let Some(service_name) = &node.path {
+                set(&mut grpc, "grpc-service-name", service_name.as_str());
+            }
+            set(proxy, "grpc-opts", grpc);
+        }
+        // TLS 下的 http 传输即 h2，否则为 HTTP 伪装
+        Some("http") if node.security.is_some() || node.protocol == "trojan" => {
+            set(proxy, "network", "h2");
+            let mut h2 = Mapping::new();
+            if let Some(host) = &node.host {
+                set(&mut h2, "host", split_hosts(host));
+            }
+            if let Some(path) = &node.path {
+                set(&mut h2, "path", path.as_str());
+            }
+            set(proxy, "h2-opts", h2);
+        }
+        Some("http") => {
+            set(proxy, "network", "http");
+            let mut http = Mapping::new();
+            if let Some(path) = &node.path {
+                set(&mut http, "path", vec![path.clone()]);
+            }
+            if let Some(host) = &node.host {
+                let mut headers = Mapping::new();
+                set(&mut headers, "Host", split_hosts(host));
+                set(&mut http, "headers", headers);
+            }
+            set(proxy, "http-opts", http);
+        }
+        _ => {}
+    }
+}
+
+// SIP003 插件参数转换为 Clash 的 plugin / plugin-opts，convert_ss_plugin 的逆操作
+fn to_clash_ss_plugin(plugin: &str, opts: Option<&str>) -> (&'static str, Mapping) {
+    let mut map = Mapping::new();
+    let params = opts
+        .unwrap_or("")
+        .split(';')
+        .filter(|p| !p.is_empty())
+        .map(|p| p.split_once('=').unwrap_or((p, "")));
+
+    if plugin == "v2ray-plugin" {
+        for (key, value) in params {
+            match key {
+                "mode" | "host" | "path" => set(&mut map, key, value),
+                "tls" => set(&mut map, "tls", true),
+                "mux" => set(&mut map, "mux", value != "0"),
+                _ => {}
+            }
+        }
+        ("v2ray-plugin", map)
+    } else {
+        for (key, value) in params {
+            match key {
+                "obfs" => set(&mut map, "mode", value),
+                "obfs-host" => set(&mut map, "host", value),
+                _ => {}
+            }
+        }
+        ("obfs", map)
+    }
+}
+
+fn split_hosts(host: &str) -> Vec<String> {
+    host.split(',')
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .collect()
+}
+
+fn set<V: Into<Value>>(map: &mut Mapping, key: &str, value: V) {
+    map.insert(Value::String(key.to_string()), value.into());
+}
+
+// --- YAML 取值辅助函数 ---
+
+// 字符串字段，数字也按字符串读取 (如 password: 123456)
+fn get_str(map: &Mapping, key: &str) -> Option<String> {
+    match map.get(key)? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn get_u16(map: &Mapping, key: &str) -> Option<u16> {
+    match map.get(key)? {
+        Value::Number(n) => u16::try_from(n.as_u64()?).ok(),
+        Value::String(s) => s.trim().parse::<u16>().ok(),
+        _ => None,
+    }
+}
+
+fn get_bool(map: &Mapping, key: &str) -> bool {
+    match map.get(key) {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::String(s)) => s == "true" || s == "1",
+        Some(Value::Number(n)) => n.as_u64() == Some(1),
+        _ => false,
+    }
+}
+
+// 列表字段，兼容写成单个字符串的情况
+fn get_str_list(map: &Mapping, key: &str) -> Option<Vec<String>> {
+    let list: Vec<String> = match map.get(key)? {
+        Value::Sequence(seq) => seq
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect(),
+        Value::String(s) => s.split(',').map(|s| s.trim().to_string()).collect(),
+        _ => return None,
+    };
+    let list: Vec<String> = list.into_iter().filter(|s| !s.is_empty()).collect();
+    if list.is_empty() {
+        None
+    } else {
+        Some(list)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::subscriptions::Node;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // ==========================================================
 // Sing-box Config
@@ -12,6 +13,20 @@ pub struct SingBoxConfig {
     pub outbounds: Vec<Outbound>,
     pub route: RouteConfig,
     pub dns: DnsConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub experimental: Option<ExperimentalConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExperimentalConfig {
+    pub clash_api: ClashApiConfig,
+}
+
+// 本地控制接口，用于运行中切换 selector 的选择
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClashApiConfig {
+    pub external_controller: String,
+    pub secret: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -20,16 +35,32 @@ pub struct LogConfig {
     pub output: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Inbound {
     #[serde(rename = "type")]
     pub inbound_type: String,
     pub tag: String,
-    pub listen: String,
-    pub listen_port: u16,
+
+    // mixed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen_port: Option<u16>,
+
+    // tun
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mtu: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_route: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict_route: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Outbound {
     #[serde(rename = "type")]
     pub outbound_type: String,
@@ -40,6 +71,8 @@ pub struct Outbound {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub server_port: Option<u16>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_ports: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uuid: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub security: Option<String>,
@@ -50,16 +83,111 @@ pub struct Outbound {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub method: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugin: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugin_opts: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flow: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packet_encoding: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub up_mbps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub down_mbps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub obfs: Option<ObfsConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub congestion_control: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub udp_relay_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zero_rtt_handshake: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tls: Option<TlsConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transport: Option<TransportConfig>,
+
+    // urltest / selector 组
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outbounds: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tolerance: Option<u16>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TransportConfig {
+    Ws {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        headers: Option<HashMap<String, String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_early_data: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        early_data_header_name: Option<String>,
+    },
+    Grpc {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        service_name: Option<String>,
+    },
+    Http {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        host: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+    },
+    Httpupgrade {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        host: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ObfsConfig {
+    #[serde(rename = "type")]
+    pub obfs_type: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct TlsConfig {
+    #[serde(default)]
     pub enabled: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub server_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub insecure: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alpn: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utls: Option<UtlsConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reality: Option<RealityConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UtlsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RealityConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    pub public_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -68,8 +196,10 @@ pub struct RouteConfig {
     pub auto_detect_interface: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct RouteRule {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inbound: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub protocol: Option<Vec<String>>,
 
@@ -83,6 +213,10 @@ pub struct RouteRule {
     pub ip_cidr: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub port: Option<Vec<u16>>,
+
+    // 规则动作 (sing-box 1.11+)，为空时即 route 到 outbound
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub outbound: Option<String>,
 }
@@ -121,72 +255,176 @@ pub struct DnsRule {
 // 生成配置逻辑
 // ==========================================================
 
+// TUN 入站参数，stack 为 "system" | "gvisor" | "mixed"
+pub struct TunOptions {
+    pub stack: String,
+    pub addresses: Vec<String>, // IPv4 / IPv6 地址段，如 172.19.0.1/30
+    pub mtu: u32,
+    pub auto_route: bool,
+    pub strict_route: bool,
+}
+
+// 代理出站：单个节点，或由多个节点组成的组
+pub enum ProxyTarget<'a> {
+    Node(&'a Node),
+    // 手动选择组，默认选中 selected，运行中可通过 clash_api 切换
+    Selector {
+        nodes: &'a [Node],
+        selected: &'a str,
+    },
+    UrlTest {
+        nodes: &'a [Node],
+        options: UrlTestOptions,
+    },
+}
+
+// urltest 参数：测速地址、测速间隔与切换容差
+pub struct UrlTestOptions {
+    pub url: String,
+    pub interval_secs: u32,
+    pub tolerance_ms: u16,
+}
+
+impl ProxyTarget<'_> {
+    fn nodes(&self) -> &[Node] {
+        match self {
+            ProxyTarget::Node(node) => std::slice::from_ref(*node),
+            ProxyTarget::Selector { nodes, .. } | ProxyTarget::UrlTest { nodes, .. } => nodes,
+        }
+    }
+
+    // 生成的出站中 tag 为 "proxy" 的就是最终使用的代理
+    fn outbounds(&self) -> Vec<Outbound> {
+        let (group, nodes) = match self {
+            ProxyTarget::Node(node) => return vec![convert_node_to_outbound(node, "proxy")],
+            ProxyTarget::Selector { nodes, selected } => {
+                (selector_outbound(nodes, selected), nodes)
+            }
+            ProxyTarget::UrlTest { nodes, options } => (urltest_outbound(nodes, options), nodes),
+        };
+
+        // 组内节点以节点 id 作为 tag，保证唯一
+        std::iter::once(group)
+            .chain(nodes.iter().map(|n| convert_node_to_outbound(n, &n.id)))
+            .collect()
+    }
+}
+
+// selector 组，成员为各节点的 id，selected 为默认选中的节点
+fn selector_outbound(nodes: &[Node], selected: &str) -> Outbound {
+    Outbound {
+        outbound_type: "selector".to_string(),
+        tag: "proxy".to_string(),
+        outbounds: Some(nodes.iter().map(|n| n.id.clone()).collect()),
+        default: Some(selected.to_string()),
+        ..Default::default()
+    }
+}
+
+// urltest 组，成员为各节点的 id
+fn urltest_outbound(nodes: &[Node], options: &UrlTestOptions) -> Outbound {
+    Outbound {
+        outbound_type: "urltest".to_string(),
+        tag: "proxy".to_string(),
+        outbounds: Some(nodes.iter().map(|n| n.id.clone()).collect()),
+        url: Some(options.url.clone()),
+        interval: Some(format!("{}s", options.interval_secs)),
+        tolerance: Some(options.tolerance_ms),
+        ..Default::default()
+    }
+}
+
 // ✅ 修改：新增 allow_lan 参数
 pub fn generate_singbox_config(
-    node: &Node,
+    target: &ProxyTarget,
     mode: &str,
     port: u16,
     whitelist: &[String],
     allow_lan: bool,
+    tun: Option<&TunOptions>,
 ) -> SingBoxConfig {
-    let proxy_outbound = convert_node_to_outbound(node);
+    let mut outbounds = target.outbounds();
+
+    // 节点服务器地址本身必须直连
+    let mut node_addresses: Vec<String> = Vec::new();
+    for node in target.nodes() {
+        if !node_addresses.contains(&node.address) {
+            node_addresses.push(node.address.clone());
+        }
+    }
 
     let direct_outbound = Outbound {
         outbound_type: "direct".to_string(),
         tag: "direct".to_string(),
-        server: None,
-        server_port: None,
-        uuid: None,
-        security: None,
-        alter_id: None,
-        password: None,
-        method: None,
-        tls: None,
+        ..Default::default()
     };
+    outbounds.push(direct_outbound);
 
     // ✅ 逻辑：如果允许局域网，监听 0.0.0.0，否则监听 127.0.0.1
     let listen_address = if allow_lan { "0.0.0.0" } else { "127.0.0.1" };
 
-    let mixed_inbound = Inbound {
+    let mut inbounds = vec![Inbound {
         inbound_type: "mixed".to_string(),
         tag: "mixed-in".to_string(),
-        listen: listen_address.to_string(),
-        listen_port: port,
-    };
+        listen: Some(listen_address.to_string()),
+        listen_port: Some(port),
+        ..Default::default()
+    }];
 
     // --- 路由规则 ---
     let mut rules = Vec::new();
 
+    // 0. TUN 模式：接管全部流量，DNS 查询交给内置 DNS 处理
+    if let Some(tun) = tun {
+        inbounds.push(Inbound {
+            inbound_type: "tun".to_string(),
+            tag: "tun-in".to_string(),
+            address: Some(tun.addresses.clone()),
+            mtu: Some(tun.mtu),
+            auto_route: Some(tun.auto_route),
+            strict_route: Some(tun.strict_route),
+            stack: Some(tun.stack.clone()),
+            ..Default::default()
+        });
+        // 先嗅探出协议与域名，DNS 查询才能命中下面的劫持规则
+        rules.push(RouteRule {
+            inbound: Some(vec!["tun-in".to_string()]),
+            action: Some("sniff".to_string()),
+            ..Default::default()
+        });
+        rules.push(RouteRule {
+            protocol: Some(vec!["dns".to_string()]),
+            action: Some("hijack-dns".to_string()),
+            ..Default::default()
+        });
+        rules.push(RouteRule {
+            port: Some(vec![53]),
+            action: Some("hijack-dns".to_string()),
+            ..Default::default()
+        });
+    }
+
     // 1. 节点域名直连
     rules.push(RouteRule {
-        protocol: None,
-        domain: Some(vec![node.address.clone()]),
-        domain_suffix: None,
-        ip_cidr: None,
-        port: None,
+        domain: Some(node_addresses.clone()),
         outbound: Some("direct".to_string()),
+        ..Default::default()
     });
 
     // 2. 规则模式处理
     if mode == "Rule" {
         if !whitelist.is_empty() {
             rules.push(RouteRule {
-                protocol: None,
-                domain: None,
                 domain_suffix: Some(whitelist.to_vec()),
-                ip_cidr: None,
-                port: None,
                 outbound: Some("direct".to_string()),
+                ..Default::default()
             });
         }
 
         rules.push(RouteRule {
-            protocol: None,
-            domain: None,
             domain_suffix: Some(vec!["cn".to_string()]),
-            ip_cidr: None,
-            port: None,
             outbound: Some("direct".to_string()),
+            ..Default::default()
         });
     }
 
@@ -196,17 +434,13 @@ pub fn generate_singbox_config(
         _ => "proxy",
     };
     rules.push(RouteRule {
-        protocol: None,
-        domain: None,
-        domain_suffix: None,
-        ip_cidr: None,
-        port: None,
         outbound: Some(final_tag.to_string()),
+        ..Default::default()
     });
 
     // --- DNS 配置 ---
     let mut dns_rules = vec![DnsRule {
-        domain: Some(vec![node.address.clone()]),
+        domain: Some(node_addresses),
         domain_suffix: None,
         outbound: None,
         server: Some("local".to_string()),
@@ -252,45 +486,398 @@ pub fn generate_singbox_config(
             level: "info".to_string(),
             output: "".to_string(),
         },
-        inbounds: vec![mixed_inbound],
-        outbounds: vec![proxy_outbound, direct_outbound],
+        inbounds,
+        outbounds,
         route: RouteConfig {
             rules,
             auto_detect_interface: true,
         },
         dns: dns_config,
+        experimental: None,
     }
 }
 
-fn convert_node_to_outbound(node: &Node) -> Outbound {
+pub fn convert_node_to_outbound(node: &Node, tag: &str) -> Outbound {
     let mut out = Outbound {
         outbound_type: node.protocol.clone(),
-        tag: "proxy".to_string(),
+        tag: tag.to_string(),
         server: Some(node.address.clone()),
         server_port: Some(node.port),
-        uuid: None,
-        security: None,
-        alter_id: None,
-        password: None,
-        method: None,
-        tls: None,
+        ..Default::default()
     };
 
     if node.protocol == "vmess" {
         out.uuid = node.uuid.clone();
-        out.security = Some("auto".to_string());
-        out.alter_id = Some(0);
+        out.security = Some(node.cipher.clone().unwrap_or_else(|| "auto".to_string()));
+        out.alter_id = Some(node.alter_id);
+        out.tls = build_tls(node);
+        out.transport = build_transport(node);
     } else if node.protocol == "trojan" {
         out.password = node.password.clone();
+        // trojan 总是启用 TLS
+        out.tls = Some(node_tls(node));
+        out.transport = build_transport(node);
+    } else if node.protocol == "ss" {
+        out.outbound_type = "shadowsocks".to_string();
+        out.password = node.password.clone();
+        out.method = node.cipher.clone();
+        out.plugin = node.plugin.clone();
+        out.plugin_opts = node.plugin_opts.clone();
+    } else if node.protocol == "vless" {
+        out.uuid = node.uuid.clone();
+        out.flow = node.flow.clone();
+        out.packet_encoding = Some("xudp".to_string());
+        out.tls = build_tls(node);
+        out.transport = build_transport(node);
+    } else if node.protocol == "hysteria2" {
+        out.password = node.password.clone();
+        out.up_mbps = node.up_mbps;
+        out.down_mbps = node.down_mbps;
+        if let Some(ports) = &node.ports {
+            // sing-box 端口范围格式为 "start:end"
+            out.server_port = None;
+            out.server_ports = Some(
+                ports
+                    .split(',')
+                    .map(|p| p.trim())
+                    .filter(|p| !p.is_empty())
+                    .map(|p| match p.split_once('-') {
+                        Some((start, end)) => format!("{}:{}", start, end),
+                        None => format!("{}:{}", p, p),
+                    })
+                    .collect(),
+            );
+        }
+        out.obfs = node.obfs.as_ref().map(|obfs_type| ObfsConfig {
+            obfs_type: obfs_type.clone(),
+            password: node.obfs_password.clone().unwrap_or_default(),
+        });
         out.tls = Some(TlsConfig {
             enabled: true,
             server_name: node.sni.clone(),
-            insecure: Some(true),
+            insecure: node.allow_insecure.then_some(true),
+            ..Default::default()
         });
-    } else if node.protocol == "ss" {
-        out.outbound_type = "shadowsocks".to_string();
+    } else if node.protocol == "tuic" {
+        out.uuid = node.uuid.clone();
         out.password = node.password.clone();
-        out.method = node.cipher.clone();
+        out.congestion_control = node.congestion_control.clone();
+        out.udp_relay_mode = node.udp_relay_mode.clone();
+        out.zero_rtt_handshake = node.zero_rtt_handshake.then_some(true);
+        out.tls = Some(TlsConfig {
+            enabled: true,
+            server_name: node.sni.clone(),
+            insecure: node.allow_insecure.then_some(true),
+            // TUIC 服务端通常要求 h3
+            alpn: Some(node.alpn.clone().unwrap_or_else(|| vec!["h3".to_string()])),
+            ..Default::default()
+        });
     }
     out
 }
+
+// vless / vmess 的 TLS 配置，由 node.security 决定是否开启
+fn build_tls(node: &Node) -> Option<TlsConfig> {
+    node.security.as_ref()?;
+    Some(node_tls(node))
+}
+
+fn node_tls(node: &Node) -> TlsConfig {
+    let is_reality = node.security.as_deref() == Some("reality");
+
+    // Reality 依赖 uTLS，未指定指纹时默认使用 chrome
+    let fingerprint = match (&node.fingerprint, is_reality) {
+        (Some(fp), _) => Some(fp.clone()),
+        (None, true) => Some("chrome".to_string()),
+        (None, false) => None,
+    };
+
+    TlsConfig {
+        enabled: true,
+        server_name: node.sni.clone(),
+        insecure: node.allow_insecure.then_some(true),
+        alpn: node.alpn.clone(),
+        utls: fingerprint.map(|fp| UtlsConfig {
+            enabled: true,
+            fingerprint: fp,
+        }),
+        reality: if is_reality {
+            node.public_key.clone().map(|pbk| RealityConfig {
+                enabled: true,
+                public_key: pbk,
+                short_id: node.short_id.clone(),
+            })
+        } else {
+            None
+        },
+    }
+}
+
+fn build_transport(node: &Node) -> Option<TransportConfig> {
+    let path = node.path.clone();
+    let host = node.host.clone();
+
+    match node.network.as_deref()? {
+        "ws" => {
+            // 路径中的 ?ed=2048 表示 early data，sing-box 需要单独配置
+            let (path, max_early_data) = match path.as_deref().and_then(|p| p.split_once("?ed=")) {
+                Some((p, ed)) => (Some(p.to_string()), ed.parse::<u32>().ok()),
+                None => (path, None),
+            };
+            Some(TransportConfig::Ws {
+                path,
+                headers: host.map(|h| HashMap::from([("Host".to_string(), h)])),
+                early_data_header_name: max_early_data
+                    .map(|_| "Sec-WebSocket-Protocol".to_string()),
+                max_early_data,
+            })
+        }
+        "grpc" => Some(TransportConfig::Grpc { service_name: path }),
+        "http" => Some(TransportConfig::Http {
+            host: host.map(|h| h.split(',').map(|s| s.trim().to_string()).collect()),
+            path,
+        }),
+        "httpupgrade" => Some(TransportConfig::Httpupgrade { host, path }),
+        _ => None,
+    }
+}
+
+// convert_node_to_outbound 的逆过程：从 sing-box 出站还原节点
+// 非代理类型 (direct / selector 等) 返回 None
+pub fn outbound_to_node(out: &Outbound) -> Option<Node> {
+    let protocol = match out.outbound_type.as_str() {
+        "vmess" | "trojan" | "vless" | "hysteria2" | "tuic" => out.outbound_type.clone(),
+        "shadowsocks" => "ss".to_string(),
+        _ => return None,
+    };
+
+    // hysteria2 端口跳跃 "start:end" 还原为 "start-end"
+    let ports = out.server_ports.as_ref().map(|list| {
+        list.iter()
+            .map(|p| match p.split_once(':') {
+                Some((start, end)) if start == end => start.to_string(),
+                Some((start, end)) => format!("{}-{}", start, end),
+                None => p.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    });
+    let port = match out.server_port {
+        Some(p) => p,
+        None => ports
+            .as_deref()?
+            .split(['-', ','])
+            .next()?
+            .parse::<u16>()
+            .ok()?,
+    };
+
+    let mut node = Node {
+        protocol,
+        name: out.tag.clone(),
+        address: out.server.clone()?,
+        port,
+        uuid: out.uuid.clone(),
+        password: out.password.clone(),
+        plugin: out.plugin.clone(),
+        plugin_opts: out.plugin_opts.clone(),
+        flow: out.flow.clone(),
+        ports,
+        up_mbps: out.up_mbps,
+        down_mbps: out.down_mbps,
+        obfs: out.obfs.as_ref().map(|o| o.obfs_type.clone()),
+        obfs_password: out.obfs.as_ref().map(|o| o.password.clone()),
+        congestion_control: out.congestion_control.clone(),
+        udp_relay_mode: out.udp_relay_mode.clone(),
+        zero_rtt_handshake: out.zero_rtt_handshake.unwrap_or(false),
+        ..Default::default()
+    };
+
+    match node.protocol.as_str() {
+        "vmess" => {
+            node.cipher = out.security.clone();
+            node.alter_id = out.alter_id.unwrap_or(0);
+        }
+        "ss" => node.cipher = out.method.clone(),
+        _ => {}
+    }
+
+    if let Some(tls) = out.tls.as_ref().filter(|t| t.enabled) {
+        node.sni = tls.server_name.clone();
+        node.allow_insecure = tls.insecure.unwrap_or(false);
+        node.alpn = tls.alpn.clone();
+        node.fingerprint = tls.utls.as_ref().map(|u| u.fingerprint.clone());
+        node.security = Some("tls".to_string());
+        if let Some(reality) = tls.reality.as_ref().filter(|r| r.enabled) {
+            node.security = Some("reality".to_string());
+            node.public_key = Some(reality.public_key.clone());
+            node.short_id = reality.short_id.clone();
+        }
+    }
+
+    match &out.transport {
+        Some(TransportConfig::Ws {
+            path,
+            headers,
+            max_early_data,
+            ..
+        }) => {
+            node.network = Some("ws".to_string());
+            node.host = headers.as_ref().and_then(|h| h.get("Host").cloned());
+            node.path = match max_early_data {
+                Some(ed) => Some(format!("{}?ed={}", path.as_deref().unwrap_or("/"), ed)),
+                None => path.clone(),
+            };
+        }
+        Some(TransportConfig::Grpc { service_name }) => {
+            node.network = Some("grpc".to_string());
+            node.path = service_name.clone();
+        }
+        Some(TransportConfig::Http { host, path }) => {
+            node.network = Some("http".to_string());
+            node.host = host.as_ref().map(|h| h.join(","));
+            node.path = path.clone();
+        }
+        Some(TransportConfig::Httpupgrade { host, path }) => {
+            node.network = Some("httpupgrade".to_string());
+            node.host = host.clone();
+            node.path = path.clone();
+        }
+        None => {}
+    }
+
+    Some(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, address: &str) -> Node {
+        Node {
+            id: id.to_string(),
+            protocol: "trojan".to_string(),
+            name: id.to_string(),
+            address: address.to_string(),
+            port: 443,
+            password: Some("pw".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn urltest_group() {
+        let nodes = vec![node("a", "x.com"), node("b", "x.com"), node("c", "y.com")];
+        let target = ProxyTarget::UrlTest {
+            nodes: &nodes,
+            options: UrlTestOptions {
+                url: "https://example.com/generate_204".to_string(),
+                interval_secs: 60,
+                tolerance_ms: 50,
+            },
+        };
+        let config = generate_singbox_config(&target, "Global", 2080, &[], false, None);
+        let value = serde_json::to_value(config).unwrap();
+
+        // urltest 组之后依次为各节点出站，tag 为节点 id
+        let outbounds = value["outbounds"].as_array().unwrap();
+        assert_eq!(outbounds[0]["type"], "urltest");
+        assert_eq!(outbounds[0]["tag"], "proxy");
+        assert_eq!(
+            outbounds[0]["outbounds"],
+            serde_json::json!(["a", "b", "c"])
+        );
+        assert_eq!(outbounds[0]["interval"], "60s");
+        assert_eq!(outbounds[0]["tolerance"], 50);
+        let tags: Vec<&str> = outbounds[1..4]
+            .iter()
+            .map(|o| o["tag"].as_str().unwrap())
+            .collect();
+        assert_eq!(tags, vec!["a", "b", "c"]);
+
+        // 所有节点地址去重后直连
+        assert_eq!(
+            value["route"]["rules"][0]["domain"],
+            serde_json::json!(["x.com", "y.com"])
+        );
+    }
+
+    #[test]
+    fn selector_group() {
+        let nodes = vec![node("a", "x.com"), node("b", "y.com")];
+        let target = ProxyTarget::Selector {
+            nodes: &nodes,
+            selected: "b",
+        };
+        let mut config = generate_singbox_config(&target, "Global", 2080, &[], false, None);
+        let value = serde_json::to_value(&config).unwrap();
+        assert!(value.get("experimental").is_none());
+        assert_eq!(value["outbounds"][0]["type"], "selector");
+        assert_eq!(value["outbounds"][0]["default"], "b");
+        assert_eq!(
+            value["outbounds"][0]["outbounds"],
+            serde_json::json!(["a", "b"])
+        );
+
+        config.experimental = Some(ExperimentalConfig {
+            clash_api: ClashApiConfig {
+                external_controller: "127.0.0.1:9090".to_string(),
+                secret: "s".to_string(),
+            },
+        });
+        let value = serde_json::to_value(&config).unwrap();
+        assert_eq!(
+            value["experimental"]["clash_api"]["external_controller"],
+            "127.0.0.1:9090"
+        );
+    }
+
+    fn tun_options() -> TunOptions {
+        TunOptions {
+            stack: "gvisor".to_string(),
+            addresses: vec!["172.19.0.1/30".to_string()],
+            mtu: 9000,
+            auto_route: true,
+            strict_route: false,
+        }
+    }
+
+    #[test]
+    fn tun_inbound() {
+        let n = node("a", "x.com");
+        let target = ProxyTarget::Node(&n);
+        let tun = tun_options();
+        let config = generate_singbox_config(&target, "Rule", 2080, &[], false, Some(&tun));
+        let value = serde_json::to_value(config).unwrap();
+
+        let inbounds = value["inbounds"].as_array().unwrap();
+        assert_eq!(inbounds[0]["type"], "mixed");
+        assert!(inbounds[0].get("mtu").is_none());
+        assert_eq!(inbounds[1]["type"], "tun");
+        assert_eq!(inbounds[1]["address"], serde_json::json!(["172.19.0.1/30"]));
+        assert_eq!(inbounds[1]["stack"], "gvisor");
+        assert_eq!(inbounds[1]["auto_route"], true);
+        assert_eq!(inbounds[1]["strict_route"], false);
+
+        // 先嗅探再劫持 DNS，排在其它路由规则之前
+        let rules = &value["route"]["rules"];
+        assert_eq!(
+            rules[0],
+            serde_json::json!({"inbound": ["tun-in"], "action": "sniff"})
+        );
+        assert_eq!(
+            rules[1],
+            serde_json::json!({"protocol": ["dns"], "action": "hijack-dns"})
+        );
+        assert_eq!(
+            rules[2],
+            serde_json::json!({"port": [53], "action": "hijack-dns"})
+        );
+        let outbounds = value["outbounds"].as_array().unwrap();
+        assert!(outbounds.iter().all(|o| o["type"] != "dns"));
+
+        let config = generate_singbox_config(&target, "Rule", 2080, &[], false, None);
+        let value = serde_json::to_value(config).unwrap();
+        assert_eq!(value["inbounds"].as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::singbox::AUTO_GROUP_ID;
+use crate::storage;
+
+// ==========================================================
+// 用户自定义代理组：故障转移 (fallback)，按顺序使用第一个可用的节点
+// 与订阅一起保存在数据目录下的 groups.json
+// ==========================================================
+
+const GROUP_MIGRATIONS: &[storage::Migration] = &[];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProxyGroup {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub group_type: String, // 目前只有 "fallback"
+    pub node_ids: Vec<String>, // 按优先级排列
+}
+
+fn get_groups_path(app: &AppHandle) -> PathBuf {
+    let mut path = app
+        .path()
+        .app_data_dir()
+        .expect("failed to get app data dir");
+    if !path.exists() {
+        let _ = fs::create_dir_all(&path);
+    }
+    path.push("groups.json");
+    path
+}
+
+#[derive(Deserialize)]
+struct GroupFile {
+    groups: Vec<ProxyGroup>,
+}
+
+pub fn load_groups(app: &AppHandle) -> Result<Vec<ProxyGroup>, String> {
+    let path = get_groups_path(app);
+    Ok(
+        storage::read_versioned_as::<GroupFile>(&path, GROUP_MIGRATIONS)?
+            .map(|file| file.groups)
+            .unwrap_or_default(),
+    )
+}
+
+fn save_groups(app: &AppHandle, groups: &[ProxyGroup]) -> Result<(), String> {
+    storage::write_versioned(
+        &get_groups_path(app),
+        GROUP_MIGRATIONS,
+        serde_json::json!({ "groups": groups }),
+    )
+}
+
+// 订阅数据迁移后节点 id 发生变化，同步更新组内保存的 id
+pub fn remap_node_ids(app: &AppHandle, remap: &HashMap<String, String>) -> Result<(), String> {
+    if remap.is_empty() {
+        return Ok(());
+    }
+    let mut groups = load_groups(app)?;
+    let mut changed = false;
+    for id in groups.iter_mut().flat_map(|g| g.node_ids.iter_mut()) {
+        if let Some(new_id) = remap.get(id) {
+            *id = new_id.clone();
+            changed = true;
+        }
+    }
+    if changed {
+        save_groups(app, &groups)?;
+    }
+    Ok(())
+}
+
+pub fn find_group(app: &AppHandle, group_id: &str) -> Result<Option<ProxyGroup>, String> {
+    Ok(load_groups(app)?.into_iter().find(|g| g.id == group_id))
+}
+
+// 检查并规整用户提交的组
+fn validate_group(group: &mut ProxyGroup) -> Result<(), String> {
+    group.name = group.name.trim().to_string();
+    if group.name.is_empty() {
+        return Err("组名称不能为空".to_string());
+    }
+    if group.id == AUTO_GROUP_ID {
+        return Err(format!("{} 为保留 id", AUTO_GROUP_ID));
+    }
+
+    match group.group_type.as_str() {
+        "fallback" => {}
+        other => return Err(format!("不支持的组类型: {}", other)),
+    }
+
+    // 去掉重复的节点，保留首次出现的顺序
+    let mut seen = HashSet::new();
+    group.node_ids.retain(|id| seen.insert(id.clone()));
+    if group.node_ids.is_empty() {
+        return Err("组内至少需要一个节点".to_string());
+    }
+    Ok(())
+}
+
+// --- Tauri 命令 ---
+
+#[tauri::command]
+pub fn get_proxy_groups(app: AppHandle) -> Result<Vec<ProxyGroup>, String> {
+    load_groups(&app)
+}
+
+// id 为空时新建，否则覆盖同 id 的组
+#[tauri::command]
+pub fn save_proxy_group(app: AppHandle, mut group: ProxyGroup) -> Result<Vec<ProxyGroup>, String> {
+    validate_group(&mut group)?;
+
+    let mut groups = load_groups(&app)?;
+    if group.id.is_empty() {
+        group.id = uuid::Uuid::new_v4().to_string();
+        groups.push(group);
+    } else if let Some(existing) = groups.iter_mut().find(|g| g.id == group.id) {
+        *existing = group;
+    } else {
+        return Err("未找到该组".to_string());
+    }
+
+    save_groups(&app, &groups)?;
+    Ok(groups)
+}
+
+#[tauri::command]
+pub fn delete_proxy_group(app: AppHandle, group_id: String) -> Result<Vec<ProxyGroup>, String> {
+    let mut groups = load_groups(&app)?;
+    groups.retain(|g| g.id != group_id);
+    save_groups(&app, &groups)?;
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(group_type: &str, node_ids: &[&str]) -> ProxyGroup {
+        ProxyGroup {
+            id: String::new(),
+            name: " 备用 ".to_string(),
+            group_type: group_type.to_string(),
+            node_ids: node_ids.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn validate_fallback_group() {
+        let mut g = group("fallback", &["b", "a", "b"]);
+        validate_group(&mut g).unwrap();
+        assert_eq!(g.name, "备用");
+        // 去重后保留优先级顺序
+        assert_eq!(g.node_ids, vec!["b", "a"]);
+    }
+
+    #[test]
+    fn reject_invalid_groups() {
+        assert!(validate_group(&mut group("fallback", &[])).is_err());
+        assert!(validate_group(&mut group("round-robin", &["a"])).is_err());
+
+        let mut g = group("fallback", &["a"]);
+        g.name = " ".to_string();
+        assert!(validate_group(&mut g).is_err());
+
+        let mut g = group("fallback", &["a"]);
+        g.id = AUTO_GROUP_ID.to_string();
+        assert!(validate_group(&mut g).is_err());
+    }
+}
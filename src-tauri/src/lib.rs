@@ -9,10 +9,15 @@ use tauri::{
 };
 
 pub mod assets;
+pub mod clash;
 pub mod config;
+pub mod export;
+pub mod groups;
 pub mod latency;
+pub mod report;
 pub mod settings;
 pub mod singbox;
+pub mod storage;
 pub mod subscriptions;
 
 #[tauri::command]
@@ -55,6 +60,24 @@ pub fn run() {
 
             let app_handle = app.handle().clone();
 
+            // =========================================================
+            // 0. 数据迁移：写回新版本的订阅文件，并同步变化的节点 id
+            // =========================================================
+            match subscriptions::migrate_storage(&app_handle) {
+                Ok(remap) => {
+                    if let Err(e) = groups::remap_node_ids(&app_handle, &remap) {
+                        println!(">>> Startup: 更新代理组中的节点 id 失败: {}", e);
+                    }
+                    *app.state::<subscriptions::NodeIdRemap>().ids.lock().unwrap() = remap;
+                }
+                Err(e) => println!(">>> Startup: 订阅数据迁移失败: {}", e),
+            }
+
+            // =========================================================
+            // 1. 订阅自动更新
+            // =========================================================
+            subscriptions::start_auto_update(app_handle.clone());
+
             // =========================================================
             // 2. 系统托盘逻辑
             // =========================================================
@@ -141,6 +164,7 @@ pub fn run() {
             Ok(())
         })
         .manage(SingBoxState::new())
+        .manage(subscriptions::NodeIdRemap::default())
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             Some(vec![]),
@@ -153,9 +177,29 @@ pub fn run() {
             subscriptions::add_subscription,
             subscriptions::delete_subscription,
             subscriptions::update_all_subscriptions,
+            subscriptions::reparse_subscriptions,
             subscriptions::toggle_subscription_enabled,
+            subscriptions::set_subscription_update_interval,
+            subscriptions::set_subscription_fetch_mode,
+            subscriptions::set_subscription_headers,
+            subscriptions::set_subscription_rules,
+            subscriptions::import_nodes_from_text,
+            subscriptions::import_nodes_from_file,
+            subscriptions::add_manual_node,
+            subscriptions::update_manual_node,
+            subscriptions::delete_manual_node,
+            subscriptions::get_duplicate_nodes,
+            subscriptions::take_node_id_remap,
+            report::get_refresh_reports,
+            groups::get_proxy_groups,
+            groups::save_proxy_group,
+            groups::delete_proxy_group,
+            export::export_subscription,
+            export::export_nodes,
+            export::get_share_link,
             singbox::start_singbox,
             singbox::stop_singbox,
+            singbox::switch_node,
             latency::tcp_ping,
             latency::http_ping,
             settings::get_settings,
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::storage;
+use crate::subscriptions::Node;
+
+// ==========================================================
+// 订阅更新报告：每次刷新后记录各订阅新增 / 移除 / 变更的节点
+// ==========================================================
+
+// 磁盘上最多保留的报告数
+const MAX_REPORTS: usize = 20;
+
+const REPORT_MIGRATIONS: &[storage::Migration] = &[];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NodeSummary {
+    pub id: String,
+    pub name: String,
+    pub protocol: String,
+    pub address: String,
+    pub port: u16,
+}
+
+impl From<&Node> for NodeSummary {
+    fn from(node: &Node) -> Self {
+        Self {
+            id: node.id.clone(),
+            name: node.name.clone(),
+            protocol: node.protocol.clone(),
+            address: node.address.clone(),
+            port: node.port,
+        }
+    }
+}
+
+// 同名节点的地址或凭据发生变化
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NodeChange {
+    pub before: NodeSummary,
+    pub after: NodeSummary,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubscriptionDiff {
+    pub url: String,
+    pub name: String,
+    pub added: Vec<NodeSummary>,
+    pub removed: Vec<NodeSummary>,
+    pub changed: Vec<NodeChange>,
+}
+
+impl SubscriptionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RefreshReport {
+    pub timestamp: i64, // Unix 时间戳 (秒)
+    pub diffs: Vec<SubscriptionDiff>,
+}
+
+impl RefreshReport {
+    pub fn has_changes(&self) -> bool {
+        self.diffs.iter().any(|d| !d.is_empty())
+    }
+}
+
+// 比较刷新前后的节点列表
+// 节点 id 由地址与凭据生成，id 不变即视为未变化；id 变化但名称相同的节点记为变更
+pub fn diff_nodes(url: &str, name: &str, old: &[Node], new: &[Node]) -> SubscriptionDiff {
+    let old_ids: HashSet<&str> = old.iter().map(|n| n.id.as_str()).collect();
+    let new_ids: HashSet<&str> = new.iter().map(|n| n.id.as_str()).collect();
+
+    let mut removed: Vec<&Node> = old
+        .iter()
+        .filter(|n| !new_ids.contains(n.id.as_str()))
+        .collect();
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+
+    for node in new.iter().filter(|n| !old_ids.contains(n.id.as_str())) {
+        match removed.iter().position(|old| old.name == node.name) {
+            Some(index) => changed.push(NodeChange {
+                before: removed.remove(index).into(),
+                after: node.into(),
+            }),
+            None => added.push(node.into()),
+        }
+    }
+
+    SubscriptionDiff {
+        url: url.to_string(),
+        name: name.to_string(),
+        added,
+        removed: removed.into_iter().map(NodeSummary::from).collect(),
+        changed,
+    }
+}
+
+fn get_reports_path(app: &AppHandle) -> PathBuf {
+    let mut path = app
+        .path()
+        .app_data_dir()
+        .expect("failed to get app data dir");
+    if !path.exists() {
+        let _ = fs::create_dir_all(&path);
+    }
+    path.push("refresh_reports.json");
+    path
+}
+
+#[derive(Deserialize)]
+struct ReportFile {
+    reports: Vec<RefreshReport>,
+}
+
+fn load_reports(app: &AppHandle) -> Result<Vec<RefreshReport>, String> {
+    let path = get_reports_path(app);
+    Ok(
+        storage::read_versioned_as::<ReportFile>(&path, REPORT_MIGRATIONS)?
+            .map(|file| file.reports)
+            .unwrap_or_default(),
+    )
+}
+
+// 追加一份报告，只保留最近 MAX_REPORTS 份
+pub fn save_report(app: &AppHandle, report: &RefreshReport) -> Result<(), String> {
+    let mut reports = load_reports(app)?;
+    reports.push(report.clone());
+    if reports.len() > MAX_REPORTS {
+        reports.drain(..reports.len() - MAX_REPORTS);
+    }
+    storage::write_versioned(
+        &get_reports_path(app),
+        REPORT_MIGRATIONS,
+        serde_json::json!({ "reports": reports }),
+    )
+}
+
+// 最近的更新报告，按时间从旧到新排列
+#[tauri::command]
+pub fn get_refresh_reports(app: AppHandle) -> Result<Vec<RefreshReport>, String> {
+    load_reports(&app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, name: &str) -> Node {
+        Node {
+            id: id.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diff_added_removed_changed() {
+        let old = [node("1", "a"), node("2", "b"), node("3", "c")];
+        let new = [node("1", "a"), node("4", "b"), node("5", "d")];
+        let diff = diff_nodes("u", "s", &old, &new);
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].name, "d");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].name, "c");
+        // 同名节点的参数变化记为修改
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].before.id, "2");
+        assert_eq!(diff.changed[0].after.id, "4");
+    }
+
+    #[test]
+    fn diff_unchanged() {
+        let nodes = [node("1", "a"), node("2", "b")];
+        let diff = diff_nodes("u", "s", &nodes, &nodes);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty());
+    }
+}
@@ -4,12 +4,111 @@ use std::net::UdpSocket;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
 
+use crate::storage;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppSettings {
     pub mixed_port: u16,
     pub whitelist: Vec<String>,
     #[serde(default)]
     pub allow_lan: bool,
+    // 订阅流量使用超过该百分比时提醒
+    #[serde(default = "default_quota_warn_percent")]
+    pub quota_warn_percent: u8,
+    // 订阅距到期不足该天数时提醒
+    #[serde(default = "default_expire_warn_days")]
+    pub expire_warn_days: u32,
+    // 订阅更新：单个请求超时 (秒)、失败重试次数、并发数
+    #[serde(default = "default_subscription_timeout_secs")]
+    pub subscription_timeout_secs: u64,
+    #[serde(default = "default_subscription_retries")]
+    pub subscription_retries: u32,
+    #[serde(default = "default_subscription_concurrency")]
+    pub subscription_concurrency: usize,
+    // 合并多个订阅中的重复节点
+    #[serde(default)]
+    pub dedup_nodes: bool,
+    // 自动选择 (urltest) 组：测速地址、测速间隔 (秒)、切换容差 (毫秒)
+    #[serde(default = "default_urltest_url")]
+    pub urltest_url: String,
+    #[serde(default = "default_urltest_interval_secs")]
+    pub urltest_interval_secs: u32,
+    #[serde(default = "default_urltest_tolerance_ms")]
+    pub urltest_tolerance_ms: u16,
+    // sing-box clash_api 监听的本地端口，用于运行中切换 selector 选中的节点
+    #[serde(default = "default_clash_api_port")]
+    pub clash_api_port: u16,
+    // TUN 模式：接管全部系统流量 (需要管理员 / CAP_NET_ADMIN 权限)
+    #[serde(default)]
+    pub tun_enabled: bool,
+    #[serde(default = "default_tun_stack")]
+    pub tun_stack: String, // "system" | "gvisor" | "mixed"
+    #[serde(default = "default_tun_inet4_address")]
+    pub tun_inet4_address: String,
+    #[serde(default = "default_tun_inet6_address")]
+    pub tun_inet6_address: String, // 留空则不分配 IPv6 地址
+    #[serde(default = "default_tun_mtu")]
+    pub tun_mtu: u32,
+    #[serde(default = "default_true")]
+    pub tun_auto_route: bool,
+    #[serde(default = "default_true")]
+    pub tun_strict_route: bool,
+}
+
+fn default_quota_warn_percent() -> u8 {
+    90
+}
+
+fn default_expire_warn_days() -> u32 {
+    3
+}
+
+fn default_subscription_timeout_secs() -> u64 {
+    15
+}
+
+fn default_subscription_retries() -> u32 {
+    2
+}
+
+fn default_subscription_concurrency() -> usize {
+    4
+}
+
+fn default_urltest_url() -> String {
+    "https://www.gstatic.com/generate_204".to_string()
+}
+
+fn default_urltest_interval_secs() -> u32 {
+    180
+}
+
+fn default_urltest_tolerance_ms() -> u16 {
+    50
+}
+
+fn default_clash_api_port() -> u16 {
+    9090
+}
+
+fn default_tun_stack() -> String {
+    "mixed".to_string()
+}
+
+fn default_tun_inet4_address() -> String {
+    "172.19.0.1/30".to_string()
+}
+
+fn default_tun_inet6_address() -> String {
+    "fdfe:dcba:9876::1/126".to_string()
+}
+
+fn default_tun_mtu() -> u32 {
+    9000
+}
+
+fn default_true() -> bool {
+    true
 }
 
 // 默认设置
@@ -24,6 +123,23 @@ impl Default for AppSettings {
                 "qq.com".to_string(),
             ],
             allow_lan: false,
+            quota_warn_percent: default_quota_warn_percent(),
+            expire_warn_days: default_expire_warn_days(),
+            subscription_timeout_secs: default_subscription_timeout_secs(),
+            subscription_retries: default_subscription_retries(),
+            subscription_concurrency: default_subscription_concurrency(),
+            dedup_nodes: false,
+            urltest_url: default_urltest_url(),
+            urltest_interval_secs: default_urltest_interval_secs(),
+            urltest_tolerance_ms: default_urltest_tolerance_ms(),
+            clash_api_port: default_clash_api_port(),
+            tun_enabled: false,
+            tun_stack: default_tun_stack(),
+            tun_inet4_address: default_tun_inet4_address(),
+            tun_inet6_address: default_tun_inet6_address(),
+            tun_mtu: default_tun_mtu(),
+            tun_auto_route: true,
+            tun_strict_route: true,
         }
     }
 }
@@ -37,23 +153,21 @@ fn get_settings_path(app: &AppHandle) -> PathBuf {
     path
 }
 
+// settings.json 的版本迁移，目前仍为第 1 版
+const SETTINGS_MIGRATIONS: &[storage::Migration] = &[];
+
+// 文件损坏时返回错误，不再静默恢复为默认设置
 #[tauri::command]
-pub fn get_settings(app: AppHandle) -> AppSettings {
+pub fn get_settings(app: AppHandle) -> Result<AppSettings, String> {
     let path = get_settings_path(&app);
-    if path.exists() {
-        let content = fs::read_to_string(path).unwrap_or_default();
-        serde_json::from_str(&content).unwrap_or_default()
-    } else {
-        AppSettings::default()
-    }
+    Ok(storage::read_versioned_as(&path, SETTINGS_MIGRATIONS)?.unwrap_or_default())
 }
 
 #[tauri::command]
 pub fn save_settings(app: AppHandle, settings: AppSettings) -> Result<(), String> {
     let path = get_settings_path(&app);
-    let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    fs::write(path, content).map_err(|e| e.to_string())?;
-    Ok(())
+    let value = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
+    storage::write_versioned(&path, SETTINGS_MIGRATIONS, value)
 }
 
 // 获取本机局域网 IP
@@ -1,42 +1,377 @@
+use std::collections::HashSet;
 use std::fs;
+use std::net::TcpListener;
 use std::path::PathBuf;
 use std::process::Command;
 use std::sync::Mutex;
+use std::time::Duration;
 use sysproxy::Sysproxy;
 use tauri::{AppHandle, Emitter, Manager, State};
 use tauri_plugin_shell::process::{CommandChild, CommandEvent};
 use tauri_plugin_shell::ShellExt;
 
 use crate::config;
-use crate::settings;
+use crate::groups;
+use crate::settings::{self, AppSettings};
 use crate::subscriptions::{self, Node};
 
 pub struct SingBoxState {
     pub process: Mutex<Option<CommandChild>>,
+    selector: Mutex<Option<SelectorControl>>,
 }
 
 impl SingBoxState {
     pub fn new() -> Self {
         Self {
             process: Mutex::new(None),
+            selector: Mutex::new(None),
         }
     }
 }
 
-fn find_node_by_id(app: &AppHandle, node_id: &str) -> Option<Node> {
-    match subscriptions::get_subscriptions(app.clone()) {
-        Ok(subs) => {
-            for sub in subs {
-                for node in sub.nodes {
-                    if node.id == node_id {
-                        return Some(node);
-                    }
-                }
+// 当前运行配置的 clash_api 地址，以及 selector 中可切换的节点
+#[derive(Clone)]
+struct SelectorControl {
+    port: u16,
+    secret: String,
+    node_ids: HashSet<String>,
+}
+
+// sing-box 是否正在运行 (由本程序启动)
+pub fn is_running(app: &AppHandle) -> bool {
+    let state = app.state::<SingBoxState>();
+    let running = state.process.lock().unwrap().is_some();
+    running
+}
+
+// 特殊节点 id：由所有已启用订阅中的节点组成的自动选择 (urltest) 组
+pub const AUTO_GROUP_ID: &str = "auto-group";
+
+// 已启用订阅中的全部节点，重复节点只保留一个
+fn enabled_nodes(app: &AppHandle) -> Result<Vec<Node>, String> {
+    let mut subs = subscriptions::load_subscriptions(app)?;
+    subs.retain(|sub| sub.enabled);
+    subscriptions::dedup_nodes(&mut subs);
+    Ok(subs.into_iter().flat_map(|sub| sub.nodes).collect())
+}
+
+// 按给定顺序取出节点，已不存在的节点跳过
+fn find_nodes_by_ids(app: &AppHandle, node_ids: &[String]) -> Result<Vec<Node>, String> {
+    let all_nodes: Vec<Node> = subscriptions::load_subscriptions(app)?
+        .into_iter()
+        .flat_map(|sub| sub.nodes)
+        .collect();
+    Ok(node_ids
+        .iter()
+        .filter_map(|id| all_nodes.iter().find(|n| &n.id == id).cloned())
+        .collect())
+}
+
+fn urltest_options(settings: &AppSettings) -> config::UrlTestOptions {
+    config::UrlTestOptions {
+        url: settings.urltest_url.clone(),
+        interval_secs: settings.urltest_interval_secs.max(10),
+        tolerance_ms: settings.urltest_tolerance_ms,
+    }
+}
+
+// 未开启 TUN 时返回 None
+fn tun_options(settings: &AppSettings) -> Result<Option<config::TunOptions>, String> {
+    if !settings.tun_enabled {
+        return Ok(None);
+    }
+    if !["system", "gvisor", "mixed"].contains(&settings.tun_stack.as_str()) {
+        return Err(format!("不支持的 TUN 协议栈: {}", settings.tun_stack));
+    }
+    let addresses: Vec<String> = [&settings.tun_inet4_address, &settings.tun_inet6_address]
+        .into_iter()
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty())
+        .map(String::from)
+        .collect();
+    if addresses.is_empty() {
+        return Err("TUN 模式至少需要一个 IPv4 或 IPv6 地址".to_string());
+    }
+    Ok(Some(config::TunOptions {
+        stack: settings.tun_stack.clone(),
+        addresses,
+        mtu: settings.tun_mtu,
+        auto_route: settings.tun_auto_route,
+        strict_route: settings.tun_strict_route,
+    }))
+}
+
+// 创建 TUN 网卡需要 CAP_NET_ADMIN：以 root 运行、本进程带有 ambient 能力，
+// 或 sing-box 可执行文件已通过 setcap 授权，否则 sing-box 启动后会立即退出
+#[cfg(target_os = "linux")]
+fn check_tun_permission() -> Result<(), String> {
+    const CAP_NET_ADMIN: u32 = 12;
+    let status = fs::read_to_string("/proc/self/status").unwrap_or_default();
+    let field = |key: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(key))
+            .map(str::trim)
+    };
+    let has_cap = |key: &str| {
+        field(key)
+            .and_then(|v| u64::from_str_radix(v, 16).ok())
+            .is_some_and(|caps| caps & (1 << CAP_NET_ADMIN) != 0)
+    };
+    // Uid 行依次为 real / effective / saved / fs
+    let is_root = field("Uid:").and_then(|v| v.split_whitespace().nth(1)) == Some("0");
+    if (is_root && has_cap("CapEff:")) || has_cap("CapAmb:") {
+        return Ok(());
+    }
+
+    let sidecar = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("singbox")));
+    let hint = format!(
+        "sudo setcap cap_net_admin,cap_net_bind_service=+ep {}",
+        sidecar
+            .as_ref()
+            .map_or("singbox".to_string(), |p| p.display().to_string())
+    );
+    match sidecar.and_then(|path| file_has_capability(&path, CAP_NET_ADMIN)) {
+        Some(true) => Ok(()),
+        Some(false) => Err(format!(
+            "TUN 模式需要 CAP_NET_ADMIN 权限。请以 root 运行，或执行: {}",
+            hint
+        )),
+        // 无法读取文件能力时不阻止启动，权限不足由 sing-box 自己报错
+        None => {
+            println!(
+                ">>> 警告：无法确认 sing-box 是否有 CAP_NET_ADMIN 权限，TUN 启动失败时请执行: {}",
+                hint
+            );
+            Ok(())
+        }
+    }
+}
+
+// 读取文件的 security.capability 扩展属性 (setcap 写入的 vfs_cap_data)
+// 返回 None 表示无法判断，如文件系统不支持扩展属性
+#[cfg(target_os = "linux")]
+fn file_has_capability(path: &std::path::Path, cap: u32) -> Option<bool> {
+    use std::os::unix::ffi::OsStrExt;
+    // magic_etc 最低位为 effective 标志
+    const VFS_CAP_FLAGS_EFFECTIVE: u32 = 0x1;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    // v2 为 20 字节，v3 额外带 4 字节 rootid
+    let mut buf = [0u8; 24];
+    let len = unsafe {
+        libc::getxattr(
+            path.as_ptr(),
+            c"security.capability".as_ptr(),
+            buf.as_mut_ptr().cast(),
+            buf.len(),
+        )
+    };
+    if len < 0 {
+        // ENODATA：文件没有设置任何能力
+        return match std::io::Error::last_os_error().raw_os_error() {
+            Some(libc::ENODATA) => Some(false),
+            _ => None,
+        };
+    }
+    if len < 12 {
+        return None;
+    }
+
+    // magic_etc 之后依次为低 32 位的 permitted / inheritable
+    let word = |i: usize| u32::from_le_bytes([buf[i], buf[i + 1], buf[i + 2], buf[i + 3]]);
+    let effective = word(0) & VFS_CAP_FLAGS_EFFECTIVE != 0;
+    Some(effective && word(4) & (1 << cap) != 0)
+}
+
+// build_config 生成的配置及运行时需要的信息
+struct BuiltConfig {
+    config: config::SingBoxConfig,
+    // 运行中可以通过 clash_api 直接切换的节点 id (目标为组时为空)
+    node_ids: Vec<String>,
+    // 故障转移组的成员，按优先级排列 (目标不是故障转移组时为空)
+    fallback: Vec<String>,
+}
+
+// 目标可以是单个节点、自动选择组或用户自定义的代理组
+// 组内任何一个节点的参数不被内核接受，整个配置都会启动失败，因此先剔除这些节点
+async fn build_config(
+    app: &AppHandle,
+    settings: &AppSettings,
+    target_id: &str,
+    mode: &str,
+) -> Result<BuiltConfig, String> {
+    let tun = tun_options(settings)?;
+    let generate = |target: &config::ProxyTarget| {
+        config::generate_singbox_config(
+            target,
+            mode,
+            settings.mixed_port,
+            &settings.whitelist,
+            settings.allow_lan,
+            tun.as_ref(),
+        )
+    };
+
+    if target_id == AUTO_GROUP_ID {
+        let nodes = enabled_nodes(app)?;
+        if nodes.is_empty() {
+            return Err("没有可用的节点".to_string());
+        }
+        let (nodes, rejected) = drop_rejected_nodes(app, &generate, nodes).await?;
+        if nodes.is_empty() {
+            return Err(format!("所有节点均不被内核接受: {}", rejected[0].1));
+        }
+        let target = config::ProxyTarget::UrlTest {
+            nodes: &nodes,
+            options: urltest_options(settings),
+        };
+        return Ok(BuiltConfig {
+            config: generate(&target),
+            node_ids: Vec::new(),
+            fallback: Vec::new(),
+        });
+    }
+
+    if let Some(group) = groups::find_group(app, target_id)? {
+        if group.group_type != "fallback" {
+            return Err(format!(
+                "不支持的组类型: {}，请将代理组 {} 改为故障转移组",
+                group.group_type, group.name
+            ));
+        }
+        let nodes = find_nodes_by_ids(app, &group.node_ids)?;
+        if nodes.is_empty() {
+            return Err(format!("代理组 {} 中的节点均已不存在", group.name));
+        }
+        let (nodes, rejected) = drop_rejected_nodes(app, &generate, nodes).await?;
+        if nodes.is_empty() {
+            return Err(format!(
+                "代理组 {} 中的节点均不被内核接受: {}",
+                group.name, rejected[0].1
+            ));
+        }
+        // sing-box 没有 fallback 出站：生成 selector，默认选中第一个节点，
+        // 运行后由 start_fallback_monitor 按顺序切换到第一个可用的节点
+        let target = config::ProxyTarget::Selector {
+            nodes: &nodes,
+            selected: &nodes[0].id,
+        };
+        return Ok(BuiltConfig {
+            config: generate(&target),
+            node_ids: Vec::new(),
+            fallback: nodes.iter().map(|n| n.id.clone()).collect(),
+        });
+    }
+
+    // 在未去重的原始数据中查找，被合并掉的重复节点 id 依然可用
+    let subs = subscriptions::load_subscriptions(app)?;
+    let node = subs
+        .iter()
+        .flat_map(|sub| &sub.nodes)
+        .find(|node| node.id == target_id)
+        .cloned()
+        .ok_or("未找到该节点")?;
+    // 所属订阅已停用的节点只生成单节点配置，不把停用订阅的节点放进 selector
+    let enabled = subs
+        .iter()
+        .filter(|sub| sub.enabled)
+        .any(|sub| sub.nodes.iter().any(|n| n.id == target_id));
+    if !enabled {
+        return Ok(BuiltConfig {
+            config: generate(&config::ProxyTarget::Node(&node)),
+            node_ids: Vec::new(),
+            fallback: Vec::new(),
+        });
+    }
+
+    // 单个节点：生成包含所有已启用节点的 selector，之后切换节点无需重启
+    let mut nodes = enabled_nodes(app)?;
+    if !nodes.iter().any(|n| n.id == node.id) {
+        nodes.push(node.clone());
+    }
+    let (nodes, rejected) = drop_rejected_nodes(app, &generate, nodes).await?;
+    if let Some((_, e)) = rejected.iter().find(|(id, _)| *id == node.id) {
+        return Err(format!("所选节点不被内核接受: {}", e));
+    }
+    let target = config::ProxyTarget::Selector {
+        nodes: &nodes,
+        selected: target_id,
+    };
+    Ok(BuiltConfig {
+        config: generate(&target),
+        node_ids: nodes.iter().map(|n| n.id.clone()).collect(),
+        fallback: Vec::new(),
+    })
+}
+
+// 用 sing-box check 找出内核不接受的节点，返回保留的节点 (顺序不变) 和被剔除的节点 id 及原因
+// 全部通过时只校验一次；否则二分查找，每个被剔除的节点大约需要 log2(n) 次校验
+async fn drop_rejected_nodes(
+    app: &AppHandle,
+    generate: impl Fn(&config::ProxyTarget) -> config::SingBoxConfig,
+    nodes: Vec<Node>,
+) -> Result<(Vec<Node>, Vec<(String, String)>), String> {
+    let mut accepted = Vec::new();
+    let mut rejected = Vec::new();
+    // 后进先出：先压入后半部分，保证按原顺序处理
+    let mut pending = vec![nodes];
+    while let Some(mut chunk) = pending.pop() {
+        let target = config::ProxyTarget::Selector {
+            nodes: &chunk,
+            selected: &chunk[0].id,
+        };
+        match check_config(app, &generate(&target)).await? {
+            None => accepted.extend(chunk),
+            Some(e) if chunk.len() == 1 => {
+                println!(">>> 节点 {} 不被内核接受，已跳过: {}", chunk[0].name, e);
+                rejected.push((chunk.remove(0).id, e));
+            }
+            Some(_) => {
+                let second = chunk.split_off(chunk.len() / 2);
+                pending.push(second);
+                pending.push(chunk);
             }
-            None
         }
-        Err(_) => None,
     }
+    Ok((accepted, rejected))
+}
+
+// 用 sing-box check 校验配置，通过时返回 None，不通过时返回内核的错误输出
+// 无法运行 sing-box 本身时返回错误
+async fn check_config(
+    app: &AppHandle,
+    config: &config::SingBoxConfig,
+) -> Result<Option<String>, String> {
+    // 同时启动时各自使用独立的临时文件
+    let path =
+        get_config_path(app).with_file_name(format!("config-check-{}.json", uuid::Uuid::new_v4()));
+    let json = serde_json::to_string(config).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| e.to_string())?;
+
+    let command = app
+        .shell()
+        .sidecar("singbox")
+        .map_err(|e| e.to_string())?
+        .args(["check", "-c", &path.to_string_lossy()]);
+    let output = command.output().await;
+    let _ = fs::remove_file(&path);
+
+    let output = output.map_err(|e| format!("无法运行 sing-box check: {}", e))?;
+    if output.status.success() {
+        Ok(None)
+    } else {
+        Ok(Some(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+// 端口被占用 (如已有 Clash 在运行) 时不启用 clash_api，以免 sing-box 启动失败
+fn port_available(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
 }
 
 fn get_config_path(app: &AppHandle) -> PathBuf {
@@ -111,34 +446,62 @@ fn force_kill_singbox() {
 
 // --- Commands ---
 
+// 异步命令在后台线程执行：生成配置时会多次运行 sing-box check，不能阻塞界面
 #[tauri::command]
-pub fn start_singbox(
+pub async fn start_singbox(
     app: AppHandle,
-    state: State<SingBoxState>,
+    state: State<'_, SingBoxState>,
     node_id: String,
     mode: String,
 ) -> Result<String, String> {
-    let mut process_guard = state.process.lock().unwrap();
-
-    if let Some(child) = process_guard.take() {
-        let _ = child.kill();
-    }
-
     // 1. 获取配置
-    let settings = settings::get_settings(app.clone());
+    let settings = settings::get_settings(app.clone())?;
     let port = settings.mixed_port;
-    let whitelist = settings.whitelist;
-    let allow_lan = settings.allow_lan;
+    #[cfg(target_os = "linux")]
+    if settings.tun_enabled {
+        check_tun_permission()?;
+    }
+
+    // 2. 生成配置 (node_id 也可以是代理组 id)，校验期间旧的核心继续运行
+    let built = build_config(&app, &settings, &node_id, &mode).await?;
 
-    // 2. 强力清理环境
+    // 3. 强力清理环境 (锁不能跨 await 持有，先取出旧进程)
+    let old_child = state.process.lock().unwrap().take();
+    if let Some(child) = old_child {
+        let _ = child.kill();
+    }
+    *state.selector.lock().unwrap() = None;
     let _ = disable_system_proxy(port);
     let _ = disable_system_proxy(2080);
     force_kill_singbox();
-    std::thread::sleep(std::time::Duration::from_millis(200));
+    tokio::time::sleep(Duration::from_millis(200)).await;
 
-    // 3. 生成配置
-    let node = find_node_by_id(&app, &node_id).ok_or("未找到该节点")?;
-    let singbox_config = config::generate_singbox_config(&node, &mode, port, &whitelist, allow_lan);
+    let mut singbox_config = built.config;
+    let mut selector_control = None;
+    if port_available(settings.clash_api_port) {
+        let secret = uuid::Uuid::new_v4().to_string();
+        singbox_config.experimental = Some(config::ExperimentalConfig {
+            clash_api: config::ClashApiConfig {
+                external_controller: format!("127.0.0.1:{}", settings.clash_api_port),
+                secret: secret.clone(),
+            },
+        });
+        selector_control = Some(SelectorControl {
+            port: settings.clash_api_port,
+            secret,
+            node_ids: built.node_ids.into_iter().collect(),
+        });
+    } else if !built.fallback.is_empty() {
+        return Err(format!(
+            "故障转移组需要通过 clash_api 切换节点，但端口 {} 已被占用",
+            settings.clash_api_port
+        ));
+    } else {
+        println!(
+            ">>> 警告：clash_api 端口 {} 已被占用，切换节点时将重启核心",
+            settings.clash_api_port
+        );
+    }
     let config_json = serde_json::to_string_pretty(&singbox_config).map_err(|e| e.to_string())?;
 
     println!(">>> 生成的配置内容:\n{}", config_json);
@@ -158,14 +521,29 @@ pub fn start_singbox(
         .map_err(|e| format!("启动失败: {}", e))?;
 
     println!(">>> 进程启动 PID: {}", child.pid());
-    *process_guard = Some(child);
+    // 同时发起的另一次启动可能已经放入了进程，以本次为准
+    if let Some(other) = state.process.lock().unwrap().replace(child) {
+        let _ = other.kill();
+    }
+    if let Some(control) = &selector_control {
+        if !built.fallback.is_empty() {
+            start_fallback_monitor(
+                app.clone(),
+                control.clone(),
+                built.fallback,
+                urltest_options(&settings),
+            );
+        }
+    }
+    *state.selector.lock().unwrap() = selector_control;
 
     // 5. 设置代理
     if mode != "Direct" {
         if let Err(e) = enable_system_proxy(port) {
             println!(">>> 警告：系统代理设置失败: {}", e);
             force_kill_singbox();
-            *process_guard = None;
+            *state.process.lock().unwrap() = None;
+            *state.selector.lock().unwrap() = None;
             let _ = disable_system_proxy(port);
             return Err(format!("系统代理失败: {}", e));
         }
@@ -205,13 +583,161 @@ pub fn start_singbox(
 pub fn stop_singbox(app: AppHandle, state: State<SingBoxState>) -> Result<String, String> {
     let mut process_guard = state.process.lock().unwrap();
 
-    let settings = settings::get_settings(app);
-    let _ = disable_system_proxy(settings.mixed_port);
+    // 设置读取失败时仍要能停止代理，按默认端口清理
+    let port = settings::get_settings(app).map_or(2080, |s| s.mixed_port);
+    let _ = disable_system_proxy(port);
 
     if let Some(child) = process_guard.take() {
         let _ = child.kill();
     }
+    *state.selector.lock().unwrap() = None;
     force_kill_singbox();
 
     Ok("已停止".to_string())
 }
+
+// 通过 clash_api 切换 selector 选中的节点，已有连接不会中断
+// 返回 false 表示当前运行的配置无法直接切换到该节点，需要调用 start_singbox 重启
+#[tauri::command]
+pub async fn switch_node(state: State<'_, SingBoxState>, node_id: String) -> Result<bool, String> {
+    if state.process.lock().unwrap().is_none() {
+        return Ok(false);
+    }
+    let control = state.selector.lock().unwrap().clone();
+    let Some(control) = control.filter(|c| c.node_ids.contains(&node_id)) else {
+        return Ok(false);
+    };
+
+    select_proxy(&clash_api_client()?, &control, &node_id).await?;
+    Ok(true)
+}
+
+fn clash_api_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(FALLBACK_PROBE_TIMEOUT_MS / 1000 + 3))
+        .no_proxy()
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+// 通过 clash_api 修改 tag 为 "proxy" 的 selector 选中的节点
+async fn select_proxy(
+    client: &reqwest::Client,
+    control: &SelectorControl,
+    node_id: &str,
+) -> Result<(), String> {
+    let response = client
+        .put(format!("http://127.0.0.1:{}/proxies/proxy", control.port))
+        .bearer_auth(&control.secret)
+        .json(&serde_json::json!({ "name": node_id }))
+        .send()
+        .await
+        .map_err(|e| format!("切换节点失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("切换节点失败: HTTP {}", response.status()));
+    }
+    Ok(())
+}
+
+// 单个节点的健康检查超时
+const FALLBACK_PROBE_TIMEOUT_MS: u64 = 5000;
+
+// 通过 clash_api 对节点做一次延迟测试，超时或失败视为不可用
+async fn probe_node(
+    client: &reqwest::Client,
+    control: &SelectorControl,
+    node_id: &str,
+    url: &str,
+) -> bool {
+    let timeout = FALLBACK_PROBE_TIMEOUT_MS.to_string();
+    client
+        .get(format!(
+            "http://127.0.0.1:{}/proxies/{}/delay",
+            control.port, node_id
+        ))
+        .bearer_auth(&control.secret)
+        .query(&[("url", url), ("timeout", timeout.as_str())])
+        .send()
+        .await
+        .is_ok_and(|response| response.status().is_success())
+}
+
+// 故障转移：每个测速周期按组内顺序测试节点，切换到第一个可用的节点
+// 排在前面的节点恢复后会切回去；核心停止或重启 (clash_api secret 变化) 后任务退出
+fn start_fallback_monitor(
+    app: AppHandle,
+    control: SelectorControl,
+    members: Vec<String>,
+    options: config::UrlTestOptions,
+) {
+    tauri::async_runtime::spawn(async move {
+        let client = match clash_api_client() {
+            Ok(client) => client,
+            Err(e) => {
+                println!(">> 故障转移监控启动失败: {}", e);
+                return;
+            }
+        };
+        // selector 默认选中第一个节点
+        let mut current = members[0].clone();
+        // 等待核心完成启动
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        loop {
+            let active = {
+                let state = app.state::<SingBoxState>();
+                let selector = state.selector.lock().unwrap();
+                selector
+                    .as_ref()
+                    .is_some_and(|c| c.secret == control.secret)
+            };
+            if !active {
+                break;
+            }
+
+            let mut healthy = None;
+            for id in &members {
+                if probe_node(&client, &control, id, &options.url).await {
+                    healthy = Some(id);
+                    break;
+                }
+            }
+            match healthy {
+                Some(id) if *id != current => match select_proxy(&client, &control, id).await {
+                    Ok(()) => {
+                        println!(">> 故障转移：切换到节点 {}", id);
+                        current = id.clone();
+                    }
+                    Err(e) => println!(">> 故障转移：{}", e),
+                },
+                Some(_) => {}
+                None => println!(">> 故障转移：组内节点均不可用"),
+            }
+
+            tokio::time::sleep(Duration::from_secs(options.interval_secs as u64)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tun_options_from_settings() {
+        let mut settings = AppSettings::default();
+        assert!(tun_options(&settings).unwrap().is_none());
+
+        settings.tun_enabled = true;
+        settings.tun_inet6_address = " ".to_string();
+        let tun = tun_options(&settings).unwrap().unwrap();
+        assert_eq!(tun.addresses, vec![settings.tun_inet4_address.clone()]);
+
+        settings.tun_inet4_address = String::new();
+        assert!(tun_options(&settings).is_err());
+
+        settings.tun_inet4_address = "172.19.0.1/30".to_string();
+        settings.tun_stack = "lwip".to_string();
+        assert!(tun_options(&settings).is_err());
+    }
+}
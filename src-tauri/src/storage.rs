@@ -0,0 +1,249 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::ffi::OsString;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// ==========================================================
+// 数据文件读写：原子写入、.bak 备份与 schema_version 迁移
+// ==========================================================
+
+// 把第 n 版数据升级到第 n + 1 版
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+// 当前版本号 = 迁移函数个数 + 1 (没有 schema_version 字段的旧文件视为第 1 版)
+fn current_version(migrations: &[Migration]) -> u64 {
+    migrations.len() as u64 + 1
+}
+
+// 在文件名后追加后缀，如 subscriptions.json -> subscriptions.json.bak
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+fn read_json(path: &Path) -> Result<Value, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("读取 {} 失败: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| corrupt_error(path, &e.to_string()))
+}
+
+fn corrupt_error(path: &Path, reason: &str) -> String {
+    format!(
+        "{} 已损坏 ({})。原文件未被修改，可从 {} 恢复",
+        path.display(),
+        reason,
+        backup_path(path).display()
+    )
+}
+
+// 版本号从 1 开始，0 或非整数视为文件损坏
+fn stored_version(path: &Path, value: &Value) -> Result<u64, String> {
+    match value.get("schema_version") {
+        None => Ok(1),
+        Some(v) => match v.as_u64() {
+            Some(version) if version >= 1 => Ok(version),
+            _ => Err(corrupt_error(path, &format!("schema_version {} 无效", v))),
+        },
+    }
+}
+
+// 文件存在且版本低于当前版本时返回 true
+pub fn needs_migration(path: &Path, migrations: &[Migration]) -> Result<bool, String> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let value = read_json(path)?;
+    Ok(stored_version(path, &value)? < current_version(migrations))
+}
+
+// 读取带版本号的 JSON 文件，文件不存在时返回 None
+// 解析失败或版本过新时返回错误，绝不回退为空数据，避免下次保存时覆盖原文件
+pub fn read_versioned(path: &Path, migrations: &[Migration]) -> Result<Option<Value>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let mut value = read_json(path)?;
+    let mut version = stored_version(path, &value)?;
+    let current = current_version(migrations);
+    if version > current {
+        return Err(format!(
+            "{} 由更新版本的程序生成 (schema_version {})，请升级后再试",
+            path.display(),
+            version
+        ));
+    }
+    while version < current {
+        println!(
+            ">> 迁移 {}: schema_version {} -> {}",
+            path.display(),
+            version,
+            version + 1
+        );
+        value = migrations[version as usize - 1](value)?;
+        version += 1;
+    }
+    Ok(Some(value))
+}
+
+// 读取带版本号的 JSON 文件并反序列化为 T，文件不存在时返回 None
+pub fn read_versioned_as<T: DeserializeOwned>(
+    path: &Path,
+    migrations: &[Migration],
+) -> Result<Option<T>, String> {
+    match read_versioned(path, migrations)? {
+        Some(value) => serde_json::from_value(value).map(Some).map_err(|e| {
+            format!(
+                "{} 格式错误 ({})。原文件未被修改，可从 {} 恢复",
+                path.display(),
+                e,
+                backup_path(path).display()
+            )
+        }),
+        None => Ok(None),
+    }
+}
+
+// 写入 JSON 对象并附带当前 schema_version
+pub fn write_versioned(
+    path: &Path,
+    migrations: &[Migration],
+    mut value: Value,
+) -> Result<(), String> {
+    let object = value.as_object_mut().ok_or("只能保存 JSON 对象")?;
+    object.insert(
+        "schema_version".to_string(),
+        Value::from(current_version(migrations)),
+    );
+    let content = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+    write_atomic(path, &content)
+}
+
+// 先写临时文件并落盘，再重命名覆盖目标文件；覆盖前把旧文件复制为 .bak
+// 写入中途崩溃时原文件保持完整。旧文件本身已损坏时不覆盖 .bak，保留最后一份可用的备份
+pub fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
+    let tmp_path = with_suffix(path, ".tmp");
+    let write_tmp = || -> std::io::Result<()> {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    };
+    write_tmp().map_err(|e| format!("写入 {} 失败: {}", tmp_path.display(), e))?;
+
+    if path.exists() && read_json(path).is_ok() {
+        let bak_path = backup_path(path);
+        fs::copy(path, &bak_path)
+            .map_err(|e| format!("备份 {} 失败: {}", bak_path.display(), e))?;
+    }
+    fs::rename(&tmp_path, path).map_err(|e| format!("保存 {} 失败: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 第 1 版为数组，第 2 版包装为对象
+    fn wrap_v1(value: Value) -> Result<Value, String> {
+        Ok(serde_json::json!({ "items": value }))
+    }
+
+    const MIGRATIONS: &[Migration] = &[wrap_v1];
+
+    fn temp_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("storage-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("data.json")
+    }
+
+    #[test]
+    fn migrate_and_write() {
+        let path = temp_file("migrate");
+        assert!(read_versioned(&path, MIGRATIONS).unwrap().is_none());
+
+        fs::write(&path, "[1,2]").unwrap();
+        let value = read_versioned(&path, MIGRATIONS).unwrap().unwrap();
+        assert_eq!(value["items"][1], 2);
+        // 读取时只在内存中迁移
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[1,2]");
+
+        write_versioned(&path, MIGRATIONS, value).unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "[1,2]");
+        assert!(!with_suffix(&path, ".tmp").exists());
+        let value = read_versioned(&path, MIGRATIONS).unwrap().unwrap();
+        assert_eq!(value["schema_version"], 2);
+        assert_eq!(value["items"][0], 1);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn reject_corrupt_or_newer_files() {
+        let path = temp_file("reject");
+
+        fs::write(&path, "{broken").unwrap();
+        let err = read_versioned(&path, MIGRATIONS).unwrap_err();
+        assert!(err.contains("已损坏"), "{}", err);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{broken");
+
+        fs::write(&path, r#"{"schema_version": 3}"#).unwrap();
+        assert!(read_versioned(&path, MIGRATIONS).is_err());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn reject_zero_schema_version() {
+        let path = temp_file("zero");
+        fs::write(&path, r#"{"schema_version": 0, "items": []}"#).unwrap();
+        let err = read_versioned(&path, MIGRATIONS).unwrap_err();
+        assert!(err.contains("已损坏"), "{}", err);
+        assert!(needs_migration(&path, MIGRATIONS).is_err());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn typed_read_reports_format_errors() {
+        #[derive(serde::Deserialize)]
+        struct Items {
+            items: Vec<u32>,
+        }
+
+        let path = temp_file("typed");
+        assert!(read_versioned_as::<Items>(&path, MIGRATIONS)
+            .unwrap()
+            .is_none());
+
+        fs::write(&path, "[1,2]").unwrap();
+        let items = read_versioned_as::<Items>(&path, MIGRATIONS)
+            .unwrap()
+            .unwrap();
+        assert_eq!(items.items, vec![1, 2]);
+
+        fs::write(&path, r#"{"schema_version": 2, "items": "x"}"#).unwrap();
+        let err = read_versioned_as::<Items>(&path, MIGRATIONS).err().unwrap();
+        assert!(err.contains("格式错误") && err.contains(".bak"), "{}", err);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn corrupt_file_does_not_replace_backup() {
+        let path = temp_file("backup");
+        fs::write(&path, r#"{"schema_version": 2, "items": [1]}"#).unwrap();
+        write_atomic(&path, r#"{"schema_version": 2, "items": [2]}"#).unwrap();
+        let good = fs::read_to_string(backup_path(&path)).unwrap();
+        assert!(good.contains("[1]"));
+
+        fs::write(&path, "{broken").unwrap();
+        write_atomic(&path, r#"{"schema_version": 2, "items": []}"#).unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), good);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -3,6 +3,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { App } from "antd";
 import { Node } from "../NodeList"; 
 import { useHomeStore } from "../../../store/homeStore";
+import { storage } from "../../../utils/storage";
 
 interface Subscription {
   url: string;
@@ -18,6 +19,13 @@ export function useNodeData() {
 
   const loadLocalNodes = async () => {
     try {
+      // 数据迁移后节点 id 可能变化，先更新上次选中的节点
+      const remap = await invoke<Record<string, string>>("take_node_id_remap");
+      const lastId = storage.getLastSelectedNodeId();
+      if (lastId && remap[lastId]) {
+        storage.setLastSelectedNodeId(remap[lastId]);
+      }
+
       const subs = await invoke<Subscription[]>("get_subscriptions");
       const activeNodes = subs
         .filter((sub) => sub.enabled)
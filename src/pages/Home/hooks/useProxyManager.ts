@@ -75,6 +75,15 @@ export function useProxyManager() {
     if (!selectedNodeId || selectedNodeId === connectedNodeId) return;
     setIsSwitching(true);
     try {
+      // 优先通过 clash_api 直接切换，不中断已有连接
+      const switched = await invoke<boolean>("switch_node", { nodeId: selectedNodeId });
+      if (switched) {
+        homeStore.setConnectedNodeId(selectedNodeId);
+        message.success("节点切换成功");
+        return;
+      }
+
+      // 当前配置中没有该节点 (如正在使用代理组)，重启核心
       isIntentionalStopRef.current = true;
       await invoke("stop_singbox");
       await invoke("start_singbox", { nodeId: selectedNodeId, mode });
@@ -19,6 +19,7 @@ import {
   CloseCircleOutlined,
   PlusOutlined,
   ShareAltOutlined,
+  GlobalOutlined,
 } from "@ant-design/icons";
 import { invoke } from "@tauri-apps/api/core";
 import { enable, isEnabled, disable } from "@tauri-apps/plugin-autostart";
@@ -29,6 +30,7 @@ interface AppSettings {
   mixed_port: number;
   whitelist: string[];
   allow_lan: boolean;
+  tun_enabled: boolean;
 }
 
 const SettingsPage: React.FC = () => {
@@ -40,6 +42,7 @@ const SettingsPage: React.FC = () => {
     mixed_port: 2080,
     whitelist: [],
     allow_lan: false,
+    tun_enabled: false,
   });
   const [newDomain, setNewDomain] = useState("");
 
@@ -50,10 +53,12 @@ const SettingsPage: React.FC = () => {
   useEffect(() => {
     isEnabled().then(setAutostart);
     invoke<boolean>("check_assets").then(setAssetsReady);
-    invoke<AppSettings>("get_settings").then((s) => {
-      setSettings(s);
-      form.setFieldsValue(s);
-    });
+    invoke<AppSettings>("get_settings")
+      .then((s) => {
+        setSettings(s);
+        form.setFieldsValue(s);
+      })
+      .catch((e) => message.error(`读取设置失败: ${e}`));
     // ✅ 获取本机 IP
     invoke<string>("get_local_ip")
       .then(setLocalIp)
@@ -81,7 +86,7 @@ const SettingsPage: React.FC = () => {
       await invoke("save_settings", { settings: newSettings });
       setSettings(newSettings);
     } catch (e) {
-      message.error("保存失败");
+      message.error(`保存失败: ${e}`);
     }
   };
 
@@ -99,6 +104,13 @@ const SettingsPage: React.FC = () => {
     message.success(checked ? "允许局域网连接已开启" : "允许局域网连接已关闭");
   };
 
+  // 处理 TUN 模式开关
+  const handleTunChange = async (checked: boolean) => {
+    const newSettings = { ...settings, tun_enabled: checked };
+    await saveAllSettings(newSettings);
+    message.success(checked ? "TUN 模式已开启，重启代理后生效" : "TUN 模式已关闭，重启代理后生效");
+  };
+
   // 资源下载
   const handleDownloadAssets = async () => {
     setLoading(true);
@@ -204,6 +216,17 @@ const SettingsPage: React.FC = () => {
               }
             />
           </List.Item>
+          <List.Item
+            extra={
+              <Switch checked={settings.tun_enabled} onChange={handleTunChange} />
+            }
+          >
+            <List.Item.Meta
+              avatar={<GlobalOutlined className="text-xl text-purple-500" />}
+              title="TUN 模式"
+              description="创建虚拟网卡接管全部系统流量，需要管理员权限 (Linux 需 CAP_NET_ADMIN)"
+            />
+          </List.Item>
         </List>
       </Card>
 
@@ -27,10 +27,24 @@ interface SubscriptionItem {
   url: string;
   name: string;
   status: string;      // active | error
-  last_updated: string; 
+  last_updated: number | null; // Unix 时间戳 (秒)
   enabled: boolean;    // 启用状态
 }
 
+// 单个订阅的节点变化
+interface SubscriptionDiff {
+  url: string;
+  name: string;
+  added: unknown[];
+  removed: unknown[];
+  changed: unknown[];
+}
+
+interface RefreshResult {
+  subscriptions: SubscriptionItem[];
+  report: { timestamp: number; diffs: SubscriptionDiff[] };
+}
+
 const SubscriptionsPage: React.FC = () => {
   const [data, setData] = useState<SubscriptionItem[]>([]);
   const [isModalOpen, setIsModalOpen] = useState(false);
@@ -48,7 +62,7 @@ const SubscriptionsPage: React.FC = () => {
       setData(res);
     } catch (error) {
       console.error(error);
-      message.error("无法加载订阅列表");
+      message.error(`无法加载订阅列表: ${error}`);
     } finally {
       setLoading(false);
     }
@@ -74,9 +88,14 @@ const SubscriptionsPage: React.FC = () => {
   const handleUpdateAll = async () => {
     message.loading({ content: "正在更新所有启用订阅...", key: "update" });
     try {
-        const updatedList = await invoke<SubscriptionItem[]>("update_all_subscriptions");
-        setData(updatedList);
-        message.success({ content: "更新完成", key: "update" });
+        const result = await invoke<RefreshResult>("update_all_subscriptions");
+        setData(result.subscriptions);
+        const count = (pick: (d: SubscriptionDiff) => unknown[]) =>
+          result.report.diffs.reduce((sum, d) => sum + pick(d).length, 0);
+        message.success({
+          content: `更新完成：新增 ${count((d) => d.added)} 个，移除 ${count((d) => d.removed)} 个，变更 ${count((d) => d.changed)} 个节点`,
+          key: "update",
+        });
     } catch (e) {
         message.error({ content: `更新失败: ${e}`, key: "update" });
     }
@@ -168,7 +187,9 @@ const SubscriptionsPage: React.FC = () => {
         return (
             <div className="flex flex-col">
                 <Tag color={color} bordered={false} className="w-fit">{text}</Tag>
-                <span className="text-xs text-gray-400 mt-1">{record.last_updated}</span>
+                <span className="text-xs text-gray-400 mt-1">
+                    {record.last_updated ? new Date(record.last_updated * 1000).toLocaleString() : "从未"}
+                </span>
             </div>
         );
       },